@@ -0,0 +1,60 @@
+use {
+    crate::grpc::MessageAccount,
+    solana_sdk::pubkey::Pubkey,
+    std::collections::HashMap,
+};
+
+#[derive(Debug, Clone, Copy)]
+struct AccountVersion {
+    slot: u64,
+    write_version: u64,
+}
+
+pub enum Admission {
+    // Better (or first) write for this pubkey - forward it.
+    Fresh,
+    // A higher (slot, write_version) is already canonical for this pubkey;
+    // this write belongs to an abandoned/competing fork.
+    Stale,
+}
+
+// Tracks, per pubkey, the best `(slot, write_version)` observed so far so
+// that rewrites arriving from a fork that lost out to a higher-ranked one
+// can be kept away from confirmed/finalized subscribers. It only ranks
+// writes; `send_loop` still delivers `Stale` writes to Processed subscribers.
+#[derive(Debug, Default)]
+pub struct ChainData {
+    accounts: HashMap<Pubkey, AccountVersion>,
+    rooted_slot: u64,
+}
+
+impl ChainData {
+    pub fn admit_account(&mut self, account: &MessageAccount) -> Admission {
+        let pubkey = account.account.pubkey;
+        let candidate = AccountVersion {
+            slot: account.slot,
+            write_version: account.account.write_version,
+        };
+
+        match self.accounts.get(&pubkey) {
+            Some(best)
+                if (best.slot, best.write_version) >= (candidate.slot, candidate.write_version) =>
+            {
+                Admission::Stale
+            }
+            _ => {
+                self.accounts.insert(pubkey, candidate);
+                Admission::Fresh
+            }
+        }
+    }
+
+    // Drop bookkeeping for anything at or below a newly rooted slot - once a
+    // slot is rooted its forks can never be reorganized away, so there's no
+    // further need to compare against it.
+    pub fn prune_rooted(&mut self, slot: u64) {
+        self.rooted_slot = self.rooted_slot.max(slot);
+        self.accounts
+            .retain(|_pubkey, version| version.slot > self.rooted_slot);
+    }
+}
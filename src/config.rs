@@ -0,0 +1,33 @@
+use std::{collections::HashMap, net::SocketAddr};
+
+#[derive(Debug, Clone)]
+pub struct ConfigGrpc {
+    pub address: SocketAddr,
+    pub channel_capacity: usize,
+    pub filters: Option<ConfigGrpcFilters>,
+    // x-token -> limits for that token. Empty means subscriptions are
+    // unauthenticated, matching the existing behavior.
+    pub x_tokens: HashMap<String, ConfigGrpcAccessLimit>,
+}
+
+// Limits applied while building a `Filter` from a client's `SubscribeRequest`,
+// so a single subscriber can't request an unbounded amount of server-side
+// filtering work.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigGrpcFilters {
+    pub accounts: ConfigGrpcFiltersAccounts,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConfigGrpcFiltersAccounts {
+    pub max_filters: Option<usize>,
+}
+
+// Per-token scoping: how many concurrent subscriptions a token may hold open,
+// and (optionally) a tighter filter config than the plugin-wide default so
+// different tokens can be handed out with different amounts of access.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigGrpcAccessLimit {
+    pub max_connections: Option<usize>,
+    pub filters: Option<ConfigGrpcFilters>,
+}
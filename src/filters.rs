@@ -0,0 +1,174 @@
+use {
+    crate::{
+        config::ConfigGrpcFilters,
+        grpc::{Message, MessageAccountInfo},
+        proto::{
+            subscribe_request_filter_accounts_filter::Filter as AccountsFilterOneof,
+            subscribe_request_filter_accounts_filter_memcmp::Data as MemcmpDataOneof,
+            SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeRequestFilterAccountsFilter,
+        },
+    },
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        collections::{HashMap, HashSet},
+        str::FromStr,
+    },
+};
+
+#[derive(Debug)]
+pub enum FilterError {
+    InvalidPubkey(String),
+    InvalidMemcmpData(String),
+}
+
+impl std::fmt::Display for FilterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPubkey(value) => write!(f, "invalid pubkey: {value}"),
+            Self::InvalidMemcmpData(value) => write!(f, "invalid memcmp data: {value}"),
+        }
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+#[derive(Debug, Clone)]
+enum AccountsDataFilter {
+    Memcmp { offset: usize, bytes: Vec<u8> },
+    Datasize(usize),
+}
+
+impl AccountsDataFilter {
+    fn try_from_proto(filter: &SubscribeRequestFilterAccountsFilter) -> Result<Self, FilterError> {
+        match &filter.filter {
+            Some(AccountsFilterOneof::Memcmp(memcmp)) => {
+                let bytes = match &memcmp.data {
+                    Some(MemcmpDataOneof::Bytes(bytes)) => bytes.clone(),
+                    Some(MemcmpDataOneof::Base58(value)) => bs58::decode(value)
+                        .into_vec()
+                        .map_err(|error| FilterError::InvalidMemcmpData(error.to_string()))?,
+                    Some(MemcmpDataOneof::Base64(value)) => base64::decode(value)
+                        .map_err(|error| FilterError::InvalidMemcmpData(error.to_string()))?,
+                    None => return Err(FilterError::InvalidMemcmpData("missing data".to_owned())),
+                };
+                Ok(Self::Memcmp {
+                    offset: memcmp.offset as usize,
+                    bytes,
+                })
+            }
+            Some(AccountsFilterOneof::Datasize(datasize)) => Ok(Self::Datasize(*datasize as usize)),
+            None => Err(FilterError::InvalidMemcmpData("missing filter".to_owned())),
+        }
+    }
+
+    fn is_match(&self, data: &[u8]) -> bool {
+        match self {
+            Self::Memcmp { offset, bytes } => match offset.checked_add(bytes.len()) {
+                Some(end) => data.get(*offset..end) == Some(bytes.as_slice()),
+                None => false,
+            },
+            Self::Datasize(size) => data.len() == *size,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct FilterAccounts {
+    account: HashSet<Pubkey>,
+    owner: HashSet<Pubkey>,
+    data_filters: Vec<AccountsDataFilter>,
+}
+
+impl FilterAccounts {
+    fn new(
+        filter: &SubscribeRequestFilterAccounts,
+        config: Option<&ConfigGrpcFilters>,
+    ) -> Result<Self, FilterError> {
+        if let Some(max_filters) = config.and_then(|config| config.accounts.max_filters) {
+            if filter.filters.len() > max_filters {
+                return Err(FilterError::InvalidMemcmpData(format!(
+                    "too many data filters: {} (max {})",
+                    filter.filters.len(),
+                    max_filters
+                )));
+            }
+        }
+
+        Ok(Self {
+            account: filter
+                .account
+                .iter()
+                .map(|value| {
+                    Pubkey::from_str(value).map_err(|_| FilterError::InvalidPubkey(value.clone()))
+                })
+                .collect::<Result<_, _>>()?,
+            owner: filter
+                .owner
+                .iter()
+                .map(|value| {
+                    Pubkey::from_str(value).map_err(|_| FilterError::InvalidPubkey(value.clone()))
+                })
+                .collect::<Result<_, _>>()?,
+            data_filters: filter
+                .filters
+                .iter()
+                .map(AccountsDataFilter::try_from_proto)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    // All clauses are ANDed: pubkey/owner (if set) and every data filter must match.
+    fn is_match(&self, account: &MessageAccountInfo) -> bool {
+        if !self.account.is_empty() && !self.account.contains(&account.pubkey) {
+            return false;
+        }
+        if !self.owner.is_empty() && !self.owner.contains(&account.owner) {
+            return false;
+        }
+        self.data_filters
+            .iter()
+            .all(|filter| filter.is_match(&account.data))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    accounts: HashMap<String, FilterAccounts>,
+    slots: HashSet<String>,
+    transactions: HashSet<String>,
+    blocks: HashSet<String>,
+}
+
+impl Filter {
+    pub fn new(
+        request: &SubscribeRequest,
+        config: Option<&ConfigGrpcFilters>,
+    ) -> Result<Self, FilterError> {
+        Ok(Self {
+            accounts: request
+                .accounts
+                .iter()
+                .map(|(name, filter)| {
+                    Ok((name.clone(), FilterAccounts::new(filter, config)?))
+                })
+                .collect::<Result<_, FilterError>>()?,
+            slots: request.slots.keys().cloned().collect(),
+            transactions: request.transactions.keys().cloned().collect(),
+            blocks: request.blocks.keys().cloned().collect(),
+        })
+    }
+
+    pub fn get_filters(&self, message: &Message) -> Vec<String> {
+        match message {
+            Message::Account(account) => self
+                .accounts
+                .iter()
+                .filter(|(_name, filter)| filter.is_match(&account.account))
+                .map(|(name, _filter)| name.clone())
+                .collect(),
+            Message::Slot(_) => self.slots.iter().cloned().collect(),
+            Message::Transaction(_) => self.transactions.iter().cloned().collect(),
+            Message::Block(_) => self.blocks.iter().cloned().collect(),
+        }
+    }
+}
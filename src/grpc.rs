@@ -1,15 +1,18 @@
 use {
     crate::{
+        chain_data::{Admission, ChainData},
         config::ConfigGrpc,
         filters::Filter,
         prom::CONNECTIONS_TOTAL,
         proto::{
             geyser_server::{Geyser, GeyserServer},
             subscribe_update::UpdateOneof,
-            SubscribeRequest, SubscribeUpdate, SubscribeUpdateAccount, SubscribeUpdateAccountInfo,
-            SubscribeUpdateBlock, SubscribeUpdateSlot, SubscribeUpdateSlotStatus,
-            SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo,
+            CommitmentLevel, SubscribeRequest, SubscribeUpdate, SubscribeUpdateAccount,
+            SubscribeUpdateAccountInfo, SubscribeUpdateBlock, SubscribeUpdateSlot,
+            SubscribeUpdateSlotStatus, SubscribeUpdateSnapshotComplete, SubscribeUpdateTransaction,
+            SubscribeUpdateTransactionInfo,
         },
+        sinks::{SinkRoute, SinkRouter},
     },
     log::*,
     solana_geyser_plugin_interface::geyser_plugin_interface::{
@@ -22,8 +25,11 @@ use {
     },
     solana_transaction_status::{Reward, TransactionStatusMeta},
     std::{
-        collections::HashMap,
-        sync::atomic::{AtomicUsize, Ordering},
+        collections::{BTreeMap, HashMap, VecDeque},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
         time::Duration,
     },
     tokio::sync::{mpsc, oneshot},
@@ -35,7 +41,7 @@ use {
     },
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MessageAccountInfo {
     pub pubkey: Pubkey,
     pub lamports: u64,
@@ -47,7 +53,7 @@ pub struct MessageAccountInfo {
     // pub txn_signature: Signature,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MessageAccount {
     pub account: MessageAccountInfo,
     pub slot: u64,
@@ -75,7 +81,7 @@ impl<'a> From<(ReplicaAccountInfoVersions<'a>, u64, bool)> for MessageAccount {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MessageSlot {
     pub slot: u64,
     pub parent: Option<u64>,
@@ -96,7 +102,7 @@ impl From<(u64, Option<u64>, SlotStatus)> for MessageSlot {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MessageTransactionInfo {
     pub signature: Signature,
     pub is_vote: bool,
@@ -105,7 +111,7 @@ pub struct MessageTransactionInfo {
     // pub index: usize,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MessageTransaction {
     pub transaction: MessageTransactionInfo,
     pub slot: u64,
@@ -128,7 +134,7 @@ impl<'a> From<(ReplicaTransactionInfoVersions<'a>, u64)> for MessageTransaction
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MessageBlock {
     pub slot: u64,
     pub blockhash: String,
@@ -151,7 +157,7 @@ impl<'a> From<ReplicaBlockInfoVersions<'a>> for MessageBlock {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Message {
     Slot(MessageSlot),
     Account(MessageAccount),
@@ -205,19 +211,480 @@ impl From<&Message> for UpdateOneof {
 struct ClientConnection {
     id: usize,
     filter: Filter,
+    commitment: CommitmentLevel,
+    from_snapshot: bool,
+    token: Option<String>,
+    // Furthest replay-buffer sequence number handed to this client's channel
+    // (or covered by a finished catch-up) so far; a send that found the
+    // channel full does not advance it. Used to resume the client from the
+    // replay buffer after a stall.
+    last_seq_sent: u64,
+    // Set while a dedicated task is draining the replay backlog to this
+    // client; live dispatch skips it until that task reports back.
+    catching_up: bool,
     stream_tx: mpsc::Sender<TonicResult<SubscribeUpdate>>,
 }
 
+// Current value of an account, held so a newly subscribed `from_snapshot`
+// client can be caught up before it starts receiving the live tail.
+#[derive(Debug, Clone)]
+struct AccountSnapshot {
+    slot: u64,
+    account: MessageAccountInfo,
+}
+
+// How many slots of inactivity an account snapshot entry may go before it's
+// evicted, so `accounts` stays bounded to recently-active accounts instead
+// of retaining every pubkey ever observed for the life of the process.
+const ACCOUNT_SNAPSHOT_WINDOW: u64 = 1_000_000;
+
+fn prune_account_snapshots(
+    accounts: &mut HashMap<Pubkey, AccountSnapshot>,
+    max_slot: u64,
+    window: u64,
+) {
+    let floor = max_slot.saturating_sub(window);
+    accounts.retain(|_pubkey, snapshot| snapshot.slot >= floor);
+}
+
+// Streams every account currently matching `client`'s filter, marked
+// `is_startup`, followed by a snapshot-complete sentinel so the client knows
+// live updates have started. Runs on a dedicated task with blocking sends,
+// so a full chain snapshot neither stalls `send_loop`'s dispatch to every
+// other client nor silently drops rows the way a non-blocking try_send
+// would while still claiming completion. The caller marks `client` as
+// `catching_up` first; live dispatch skips it until `catchup_done_tx`
+// reports this task done, at which point `handle_catchup_done` replays
+// anything that arrived in the meantime, same as a replay-buffer catch-up.
+fn spawn_snapshot(
+    client: &ClientConnection,
+    accounts: HashMap<Pubkey, AccountSnapshot>,
+    caught_up_to: u64,
+    catchup_done_tx: mpsc::UnboundedSender<(usize, u64)>,
+) {
+    let id = client.id;
+    let filter = client.filter.clone();
+    let stream_tx = client.stream_tx.clone();
+
+    tokio::spawn(async move {
+        for snapshot in accounts.values() {
+            let message = Message::Account(MessageAccount {
+                account: snapshot.account.clone(),
+                slot: snapshot.slot,
+                is_startup: true,
+            });
+
+            let filters = filter.get_filters(&message);
+            if filters.is_empty() {
+                continue;
+            }
+
+            let update = SubscribeUpdate {
+                filters,
+                update_oneof: Some((&message).into()),
+            };
+            if stream_tx.send(Ok(update)).await.is_err() {
+                return;
+            }
+        }
+
+        let complete = SubscribeUpdate {
+            filters: vec![],
+            update_oneof: Some(UpdateOneof::SnapshotComplete(SubscribeUpdateSnapshotComplete {})),
+        };
+        if stream_tx.send(Ok(complete)).await.is_err() {
+            return;
+        }
+
+        let _ = catchup_done_tx.send((id, caught_up_to));
+    });
+}
+
+// How many slots worth of processed-level messages we hold onto waiting for
+// a confirmed/finalized subscriber's commitment to be satisfied. Slots that
+// fall outside this window before being finalized (skipped/abandoned forks,
+// or confirmed slots whose root arrives too late) are dropped rather than
+// buffered forever.
+const SLOT_COMMITMENT_BUFFER_WINDOW: u64 = 100;
+
+// Account/transaction/block messages are always produced at processed time.
+// Confirmed/finalized subscribers can't be served immediately, so their
+// messages sit here keyed by slot until a matching `MessageSlot` status
+// arrives for that slot.
+#[derive(Debug, Default)]
+struct SlotMessageBuffer {
+    slots: BTreeMap<u64, Vec<Message>>,
+}
+
+impl SlotMessageBuffer {
+    fn push(&mut self, slot: u64, message: Message) {
+        self.slots.entry(slot).or_default().push(message);
+    }
+
+    // Every slot <= `max_slot`, not just `max_slot` itself - a coalesced or
+    // skipped root can announce status for a slot without ever announcing
+    // one for slots buffered underneath it, and those would otherwise be
+    // silently dropped once they age out of `prune`'s window. Confirmed is
+    // not terminal for a slot - Finalized still needs these messages
+    // afterward - so this clones them out rather than removing them.
+    fn peek_upto(&self, max_slot: u64) -> Vec<Message> {
+        self.slots
+            .range(..=max_slot)
+            .flat_map(|(_slot, messages)| messages.iter().cloned())
+            .collect()
+    }
+
+    // Same range as `peek_upto`, but finalized is terminal for these slots:
+    // nothing buffered for them will ever be needed again, so this removes
+    // them.
+    fn take_upto(&mut self, max_slot: u64) -> Vec<Message> {
+        let slots: Vec<u64> = self.slots.range(..=max_slot).map(|(&slot, _)| slot).collect();
+        slots
+            .into_iter()
+            .flat_map(|slot| self.slots.remove(&slot).unwrap_or_default())
+            .collect()
+    }
+
+    fn prune(&mut self, max_slot: u64, window: u64) {
+        let floor = max_slot.saturating_sub(window);
+        self.slots.retain(|slot, _| *slot >= floor);
+    }
+}
+
+// Several processed-level rewrites of the same account can pile up in a
+// slot's buffer before it confirms; a confirmed/finalized subscriber only
+// cares about the final value, so keep just the highest write_version per
+// pubkey and pass every other buffered message through untouched.
+fn collapse_confirmed_accounts(messages: Vec<Message>) -> Vec<Message> {
+    let mut latest_accounts: HashMap<Pubkey, MessageAccount> = HashMap::new();
+    let mut others = Vec::with_capacity(messages.len());
+
+    for message in messages {
+        match message {
+            Message::Account(account) => {
+                let keep = match latest_accounts.get(&account.account.pubkey) {
+                    Some(existing) => account.account.write_version > existing.account.write_version,
+                    None => true,
+                };
+                if keep {
+                    latest_accounts.insert(account.account.pubkey, account);
+                }
+            }
+            other => others.push(other),
+        }
+    }
+
+    others.extend(latest_accounts.into_values().map(Message::Account));
+    others
+}
+
+fn release_token(token_connections: &Mutex<HashMap<String, usize>>, token: Option<&str>) {
+    if let Some(token) = token {
+        let mut counts = token_connections.lock().unwrap();
+        if let Some(count) = counts.get_mut(token) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+const REPLAY_BUFFER_CAPACITY: usize = 2048;
+
+// Recent broadcast messages, kept so a client whose channel briefly fills up
+// (GC pause, network hiccup) can be caught up from a dedicated task instead
+// of being disconnected outright.
+#[derive(Debug, Default)]
+struct ReplayBuffer {
+    entries: VecDeque<(u64, Message)>,
+    // Seqs start at 1, so a `latest_seq()` of 0 means nothing was pushed yet.
+    last_seq: u64,
+}
+
+impl ReplayBuffer {
+    fn push(&mut self, message: Message) -> u64 {
+        self.last_seq += 1;
+        let seq = self.last_seq;
+        self.entries.push_back((seq, message));
+        if self.entries.len() > REPLAY_BUFFER_CAPACITY {
+            self.entries.pop_front();
+        }
+        seq
+    }
+
+    fn latest_seq(&self) -> u64 {
+        self.last_seq
+    }
+
+    // `None` means `from` already fell out of the window: a true overflow
+    // that the caller must handle by disconnecting rather than replaying.
+    fn since(&self, from: u64) -> Option<Vec<Message>> {
+        match self.entries.front() {
+            None => Some(Vec::new()),
+            Some((oldest, _)) if from + 1 < *oldest => None,
+            Some(_) => Some(
+                self.entries
+                    .iter()
+                    .filter(|(seq, _)| *seq > from)
+                    .map(|(_, message)| message.clone())
+                    .collect(),
+            ),
+        }
+    }
+}
+
+// One `ReplayBuffer` per commitment tier, each holding exactly what a
+// subscriber at that commitment received live - nothing more. A single
+// shared buffer fed from the raw processed-time stream can't support that: a
+// confirmed/finalized client catching up would replay processed-only writes
+// (and stale fork writes - see the dispatch_to_commitment call sites below)
+// it never would have seen live.
+#[derive(Debug, Default)]
+struct CommitmentReplayBuffers {
+    processed: ReplayBuffer,
+    confirmed: ReplayBuffer,
+    finalized: ReplayBuffer,
+}
+
+impl CommitmentReplayBuffers {
+    fn buffer(&self, commitment: CommitmentLevel) -> &ReplayBuffer {
+        match commitment {
+            CommitmentLevel::Processed => &self.processed,
+            CommitmentLevel::Confirmed => &self.confirmed,
+            CommitmentLevel::Finalized => &self.finalized,
+        }
+    }
+
+    fn buffer_mut(&mut self, commitment: CommitmentLevel) -> &mut ReplayBuffer {
+        match commitment {
+            CommitmentLevel::Processed => &mut self.processed,
+            CommitmentLevel::Confirmed => &mut self.confirmed,
+            CommitmentLevel::Finalized => &mut self.finalized,
+        }
+    }
+
+    // Records `message` in `commitment`'s own replay buffer and returns the
+    // seq it was assigned there. Always called at the point `message` is
+    // actually dispatched to that tier, so the buffer mirrors live delivery
+    // exactly.
+    fn push(&mut self, commitment: CommitmentLevel, message: Message) -> u64 {
+        self.buffer_mut(commitment).push(message)
+    }
+}
+
+fn dispatch_to_commitment(
+    clients: &mut HashMap<usize, ClientConnection>,
+    replay_buffers: &mut CommitmentReplayBuffers,
+    message: &Message,
+    commitment: CommitmentLevel,
+    ids_full: &mut Vec<usize>,
+    ids_closed: &mut Vec<usize>,
+) {
+    let seq = replay_buffers.push(commitment, message.clone());
+
+    for client in clients.values_mut() {
+        if client.commitment != commitment || client.catching_up {
+            continue;
+        }
+
+        let filters = client.filter.get_filters(message);
+        if !filters.is_empty() {
+            match client.stream_tx.try_send(Ok(SubscribeUpdate {
+                filters,
+                update_oneof: Some(message.into()),
+            })) {
+                Ok(()) => client.last_seq_sent = client.last_seq_sent.max(seq),
+                Err(mpsc::error::TrySendError::Full(_)) => ids_full.push(client.id),
+                Err(mpsc::error::TrySendError::Closed(_)) => ids_closed.push(client.id),
+            }
+        }
+    }
+}
+
+// Slot status is cumulative (Processed -> Confirmed -> Rooted), so unlike
+// account/transaction/block dispatch this delivers to every client whose
+// requested commitment is at or below the status being announced, not just
+// clients that asked for this exact level. It's recorded in every tier's
+// replay buffer at or below that status for the same reason, each getting
+// its own seq in that tier so a lagging client of any commitment can be
+// caught up from its own buffer later.
+fn dispatch_slot_status(
+    clients: &mut HashMap<usize, ClientConnection>,
+    replay_buffers: &mut CommitmentReplayBuffers,
+    message: &Message,
+    commitment: CommitmentLevel,
+    ids_full: &mut Vec<usize>,
+    ids_closed: &mut Vec<usize>,
+) {
+    let tiers = [
+        CommitmentLevel::Processed,
+        CommitmentLevel::Confirmed,
+        CommitmentLevel::Finalized,
+    ];
+    let seqs: Vec<(CommitmentLevel, u64)> = tiers
+        .into_iter()
+        .filter(|tier| *tier as i32 <= commitment as i32)
+        .map(|tier| (tier, replay_buffers.push(tier, message.clone())))
+        .collect();
+
+    for client in clients.values_mut() {
+        if client.catching_up {
+            continue;
+        }
+        let seq = match seqs.iter().find(|(tier, _)| *tier == client.commitment) {
+            Some((_, seq)) => *seq,
+            None => continue,
+        };
+
+        let filters = client.filter.get_filters(message);
+        if !filters.is_empty() {
+            match client.stream_tx.try_send(Ok(SubscribeUpdate {
+                filters,
+                update_oneof: Some(message.into()),
+            })) {
+                Ok(()) => client.last_seq_sent = client.last_seq_sent.max(seq),
+                Err(mpsc::error::TrySendError::Full(_)) => ids_full.push(client.id),
+                Err(mpsc::error::TrySendError::Closed(_)) => ids_closed.push(client.id),
+            }
+        }
+    }
+}
+
+// Drains `backlog` to `client` from a dedicated task via blocking sends, so a
+// slow consumer applies backpressure only to its own catch-up rather than
+// stalling `send_loop`. Reports back through `catchup_done_tx` once done (or
+// silently gives up if the client disconnects mid-replay).
+fn spawn_replay(
+    client: &ClientConnection,
+    backlog: Vec<Message>,
+    caught_up_to: u64,
+    catchup_done_tx: mpsc::UnboundedSender<(usize, u64)>,
+) {
+    let id = client.id;
+    let filter = client.filter.clone();
+    let stream_tx = client.stream_tx.clone();
+
+    tokio::spawn(async move {
+        for message in &backlog {
+            let filters = filter.get_filters(message);
+            if filters.is_empty() {
+                continue;
+            }
+
+            let update = SubscribeUpdate {
+                filters,
+                update_oneof: Some(message.into()),
+            };
+            if stream_tx.send(Ok(update)).await.is_err() {
+                return;
+            }
+        }
+        let _ = catchup_done_tx.send((id, caught_up_to));
+    });
+}
+
+// Called when `client_id`'s channel was full. Starts (or lets run) a replay
+// catch-up if the client's backlog is still within the buffer's window,
+// otherwise disconnects it - it has truly fallen too far behind to recover.
+// Draws from `client`'s own commitment tier, so the backlog it replays is
+// exactly what it would have seen live - never a raw, commitment-blind feed.
+fn handle_lagging_client(
+    client_id: usize,
+    clients: &mut HashMap<usize, ClientConnection>,
+    replay_buffers: &CommitmentReplayBuffers,
+    token_connections: &Mutex<HashMap<String, usize>>,
+    catchup_done_tx: &mpsc::UnboundedSender<(usize, u64)>,
+) {
+    let (commitment, last_seq_sent) = match clients.get(&client_id) {
+        Some(client) if client.catching_up => return,
+        Some(client) => (client.commitment, client.last_seq_sent),
+        None => return,
+    };
+    let replay_buffer = replay_buffers.buffer(commitment);
+
+    match replay_buffer.since(last_seq_sent) {
+        Some(backlog) => {
+            let caught_up_to = replay_buffer.latest_seq();
+            if let Some(client) = clients.get_mut(&client_id) {
+                client.catching_up = true;
+                spawn_replay(client, backlog, caught_up_to, catchup_done_tx.clone());
+            }
+        }
+        None => {
+            if let Some(client) = clients.remove(&client_id) {
+                release_token(token_connections, client.token.as_deref());
+                tokio::spawn(async move {
+                    CONNECTIONS_TOTAL.dec();
+                    error!("{}, lagged past replay buffer, close stream", client.id);
+                    let _ = client.stream_tx.send(Err(Status::internal("lagged"))).await;
+                });
+            }
+        }
+    }
+}
+
+// Called when a catch-up task reports back after draining `backlog` up to
+// `caught_up_to`. Messages can arrive (and be skipped by live dispatch,
+// since the client is still `catching_up`) while that task is running, so
+// this keeps spawning further replay rounds until the client has reached
+// the buffer's actual latest seq, instead of clearing `catching_up` with a
+// gap still outstanding. Draws from `client`'s own commitment tier, same as
+// `handle_lagging_client`.
+fn handle_catchup_done(
+    client_id: usize,
+    caught_up_to: u64,
+    clients: &mut HashMap<usize, ClientConnection>,
+    replay_buffers: &CommitmentReplayBuffers,
+    token_connections: &Mutex<HashMap<String, usize>>,
+    catchup_done_tx: &mpsc::UnboundedSender<(usize, u64)>,
+) {
+    let commitment = if let Some(client) = clients.get_mut(&client_id) {
+        client.last_seq_sent = client.last_seq_sent.max(caught_up_to);
+        client.commitment
+    } else {
+        return;
+    };
+    let replay_buffer = replay_buffers.buffer(commitment);
+
+    if replay_buffer.latest_seq() == caught_up_to {
+        if let Some(client) = clients.get_mut(&client_id) {
+            client.catching_up = false;
+        }
+        info!("{}, caught up via replay buffer", client_id);
+        return;
+    }
+
+    match replay_buffer.since(caught_up_to) {
+        Some(backlog) => {
+            let next_caught_up_to = replay_buffer.latest_seq();
+            if let Some(client) = clients.get(&client_id) {
+                spawn_replay(client, backlog, next_caught_up_to, catchup_done_tx.clone());
+            }
+        }
+        None => {
+            if let Some(client) = clients.remove(&client_id) {
+                release_token(token_connections, client.token.as_deref());
+                tokio::spawn(async move {
+                    CONNECTIONS_TOTAL.dec();
+                    error!("{}, lagged past replay buffer, close stream", client.id);
+                    let _ = client.stream_tx.send(Err(Status::internal("lagged"))).await;
+                });
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct GrpcService {
     config: ConfigGrpc,
     subscribe_id: AtomicUsize,
     new_clients_tx: mpsc::UnboundedSender<ClientConnection>,
+    // live subscription count per x-token, to enforce ConfigGrpcAccessLimit::max_connections
+    token_connections: Arc<Mutex<HashMap<String, usize>>>,
 }
 
 impl GrpcService {
     pub fn create(
         config: ConfigGrpc,
+        sink_routes: Vec<SinkRoute>,
     ) -> Result<
         (mpsc::UnboundedSender<Message>, oneshot::Sender<()>),
         Box<dyn std::error::Error + Send + Sync>,
@@ -231,17 +698,22 @@ impl GrpcService {
 
         // Create Server
         let (new_clients_tx, new_clients_rx) = mpsc::unbounded_channel();
+        let token_connections = Arc::new(Mutex::new(HashMap::new()));
         let service = GeyserServer::new(Self {
             config,
             subscribe_id: AtomicUsize::new(0),
             new_clients_tx,
+            token_connections: Arc::clone(&token_connections),
         })
         .accept_compressed(CompressionEncoding::Gzip)
         .send_compressed(CompressionEncoding::Gzip);
 
         // Run filter and send loop
+        let sink_router = Arc::new(SinkRouter::new(sink_routes));
         let (update_channel_tx, update_channel_rx) = mpsc::unbounded_channel();
-        tokio::spawn(async move { Self::send_loop(update_channel_rx, new_clients_rx).await });
+        tokio::spawn(async move {
+            Self::send_loop(update_channel_rx, new_clients_rx, sink_router, token_connections).await
+        });
 
         // Run Server
         let (shutdown_tx, shutdown_rx) = oneshot::channel();
@@ -261,46 +733,131 @@ impl GrpcService {
     async fn send_loop(
         mut update_channel_rx: mpsc::UnboundedReceiver<Message>,
         mut new_clients_rx: mpsc::UnboundedReceiver<ClientConnection>,
+        sink_router: Arc<SinkRouter>,
+        token_connections: Arc<Mutex<HashMap<String, usize>>>,
     ) {
         let mut clients: HashMap<usize, ClientConnection> = HashMap::new();
+        let mut buffer = SlotMessageBuffer::default();
+        let mut chain_data = ChainData::default();
+        let mut accounts: HashMap<Pubkey, AccountSnapshot> = HashMap::new();
+        let mut replay_buffers = CommitmentReplayBuffers::default();
+        let (catchup_done_tx, mut catchup_done_rx) = mpsc::unbounded_channel::<(usize, u64)>();
+        let mut max_slot_seen = 0u64;
         loop {
             tokio::select! {
                 Some(message) = update_channel_rx.recv() => {
                     let mut ids_full = vec![];
                     let mut ids_closed = vec![];
 
-                    for client in clients.values() {
-                        let filters = client.filter.get_filters(&message);
-                        if !filters.is_empty() {
-                            match client.stream_tx.try_send(Ok(SubscribeUpdate {
-                                filters,
-                                update_oneof: Some((&message).into()),
-                            })) {
-                                Ok(()) => {},
-                                Err(mpsc::error::TrySendError::Full(_)) => ids_full.push(client.id),
-                                Err(mpsc::error::TrySendError::Closed(_)) => ids_closed.push(client.id),
+                    // A write that lost out to a higher (slot, write_version) already
+                    // recorded for this pubkey on a competing fork still reaches
+                    // Processed subscribers live, since that commitment is inherently
+                    // speculative - but it's excluded from the confirmed/finalized
+                    // buffer below, so it never reaches a subscriber waiting on a
+                    // settled commitment.
+                    let is_stale_account = if let Message::Account(ref account_message) = message {
+                        matches!(chain_data.admit_account(account_message), Admission::Stale)
+                    } else {
+                        false
+                    };
+
+                    match message {
+                        Message::Slot(slot_message) => {
+                            max_slot_seen = max_slot_seen.max(slot_message.slot);
+                            buffer.prune(max_slot_seen, SLOT_COMMITMENT_BUFFER_WINDOW);
+
+                            let slot = slot_message.slot;
+                            let commitment = match slot_message.status {
+                                SubscribeUpdateSlotStatus::Processed => CommitmentLevel::Processed,
+                                SubscribeUpdateSlotStatus::Confirmed => CommitmentLevel::Confirmed,
+                                SubscribeUpdateSlotStatus::Rooted => CommitmentLevel::Finalized,
+                            };
+                            let message = Message::Slot(slot_message);
+                            dispatch_slot_status(&mut clients, &mut replay_buffers, &message, commitment, &mut ids_full, &mut ids_closed);
+
+                            if commitment == CommitmentLevel::Finalized {
+                                chain_data.prune_rooted(slot);
+                                prune_account_snapshots(&mut accounts, slot, ACCOUNT_SNAPSHOT_WINDOW);
+                            }
+
+                            // Confirmed/rooted status means every buffered processed-level
+                            // message for this slot, and any earlier slot still sitting in
+                            // the buffer (a coalesced or skipped root covers them too), can
+                            // now be released to subscribers waiting on that commitment.
+                            // Collapse repeated processed-level rewrites of the same account
+                            // into their final value first.
+                            if matches!(commitment, CommitmentLevel::Confirmed | CommitmentLevel::Finalized) {
+                                // Confirmed only peeks the buffer (Finalized still needs
+                                // it); Finalized is terminal for these slots and takes them.
+                                let buffered = if commitment == CommitmentLevel::Finalized {
+                                    buffer.take_upto(slot)
+                                } else {
+                                    buffer.peek_upto(slot)
+                                };
+                                for buffered in collapse_confirmed_accounts(buffered) {
+                                    dispatch_to_commitment(&mut clients, &mut replay_buffers, &buffered, commitment, &mut ids_full, &mut ids_closed);
+                                }
+                            }
+                        }
+                        Message::Account(account_message) => {
+                            // Same admission check that gates the confirmed/finalized
+                            // buffer below: a fork-losing write must not become the
+                            // "current" value a from_snapshot client is served either.
+                            if !is_stale_account {
+                                accounts.insert(
+                                    account_message.account.pubkey,
+                                    AccountSnapshot {
+                                        slot: account_message.slot,
+                                        account: account_message.account.clone(),
+                                    },
+                                );
+                            }
+                            sink_router.route(&account_message.account.pubkey, &account_message.account);
+
+                            let slot = account_message.slot;
+                            let message = Message::Account(account_message);
+                            dispatch_to_commitment(&mut clients, &mut replay_buffers, &message, CommitmentLevel::Processed, &mut ids_full, &mut ids_closed);
+                            if !is_stale_account {
+                                buffer.push(slot, message);
                             }
                         }
+                        Message::Transaction(transaction_message) => {
+                            let slot = transaction_message.slot;
+                            let message = Message::Transaction(transaction_message);
+                            dispatch_to_commitment(&mut clients, &mut replay_buffers, &message, CommitmentLevel::Processed, &mut ids_full, &mut ids_closed);
+                            buffer.push(slot, message);
+                        }
+                        Message::Block(block_message) => {
+                            let slot = block_message.slot;
+                            let message = Message::Block(block_message);
+                            dispatch_to_commitment(&mut clients, &mut replay_buffers, &message, CommitmentLevel::Processed, &mut ids_full, &mut ids_closed);
+                            buffer.push(slot, message);
+                        }
                     }
 
                     for id in ids_full {
-                        if let Some(client) = clients.remove(&id) {
-                            tokio::spawn(async move {
-                                CONNECTIONS_TOTAL.dec();
-                                error!("{}, lagged, close stream", client.id);
-                                let _ = client.stream_tx.send(Err(Status::internal("lagged"))).await;
-                            });
-                        }
+                        handle_lagging_client(id, &mut clients, &replay_buffers, &token_connections, &catchup_done_tx);
                     }
                     for id in ids_closed {
                         if let Some(client) = clients.remove(&id) {
+                            release_token(&token_connections, client.token.as_deref());
                             CONNECTIONS_TOTAL.dec();
                             error!("{}, client closed stream", client.id);
                         }
                     }
                 },
+                Some((id, caught_up_to)) = catchup_done_rx.recv() => {
+                    handle_catchup_done(id, caught_up_to, &mut clients, &replay_buffers, &token_connections, &catchup_done_tx);
+                }
                 Some(client) = new_clients_rx.recv() => {
+                    let mut client = client;
+                    let caught_up_to = replay_buffers.buffer(client.commitment).latest_seq();
+                    client.last_seq_sent = caught_up_to;
                     info!("{}, add client to receivers", client.id);
+                    if client.from_snapshot {
+                        client.catching_up = true;
+                        spawn_snapshot(&client, accounts.clone(), caught_up_to, catchup_done_tx.clone());
+                    }
                     clients.insert(client.id, client);
                     CONNECTIONS_TOTAL.inc();
                 }
@@ -321,21 +878,68 @@ impl Geyser for GrpcService {
         let id = self.subscribe_id.fetch_add(1, Ordering::SeqCst);
         info!("{}, new subscriber", id);
 
-        let filter = match Filter::new(request.get_ref(), self.config.filters.as_ref()) {
+        let token = if self.config.x_tokens.is_empty() {
+            None
+        } else {
+            match request
+                .metadata()
+                .get("x-token")
+                .and_then(|value| value.to_str().ok())
+                .filter(|token| self.config.x_tokens.contains_key(*token))
+            {
+                Some(token) => Some(token.to_owned()),
+                None => {
+                    error!("{}, rejected: missing or unknown x-token", id);
+                    return Err(Status::unauthenticated("missing or unknown x-token"));
+                }
+            }
+        };
+
+        let access_limit = token
+            .as_ref()
+            .and_then(|token| self.config.x_tokens.get(token));
+
+        if let Some(limit) = access_limit {
+            if let Some(max_connections) = limit.max_connections {
+                let mut counts = self.token_connections.lock().unwrap();
+                let count = counts.entry(token.clone().unwrap()).or_insert(0);
+                if *count >= max_connections {
+                    error!("{}, rejected: token at max_connections ({})", id, max_connections);
+                    return Err(Status::resource_exhausted("max concurrent connections for x-token"));
+                }
+                *count += 1;
+            }
+        }
+
+        let filters_config = access_limit
+            .and_then(|limit| limit.filters.as_ref())
+            .or(self.config.filters.as_ref());
+
+        let filter = match Filter::new(request.get_ref(), filters_config) {
             Ok(filter) => filter,
             Err(error) => {
+                release_token(&self.token_connections, token.as_deref());
                 let message = format!("failed to create filter: {:?}", error);
                 error!("{}, {}", id, message);
                 return Err(Status::invalid_argument(message));
             }
         };
 
+        let commitment = request.get_ref().commitment();
+        let from_snapshot = request.get_ref().from_snapshot;
+
         let (stream_tx, stream_rx) = mpsc::channel(self.config.channel_capacity);
-        if let Err(_error) = self.new_clients_tx.send(ClientConnection {
+        if let Err(error) = self.new_clients_tx.send(ClientConnection {
             id,
             filter,
+            commitment,
+            from_snapshot,
+            token,
+            last_seq_sent: 0,
+            catching_up: false,
             stream_tx,
         }) {
+            release_token(&self.token_connections, error.0.token.as_deref());
             return Err(Status::internal(""));
         }
 
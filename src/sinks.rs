@@ -0,0 +1,84 @@
+use {
+    crate::grpc::MessageAccountInfo,
+    log::*,
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+        time::Duration,
+    },
+    tokio::{sync::mpsc, time::Instant},
+};
+
+// Lets a plugin push selected account state into an external store (Postgres,
+// Redis, a webhook, ...) without the consumer needing a full gRPC
+// subscription.
+#[tonic::async_trait]
+pub trait AccountWriteSink: Send + Sync {
+    async fn process(&self, pubkey: &Pubkey, account: &MessageAccountInfo) -> Result<(), String>;
+}
+
+pub struct SinkRoute {
+    pub matched_pubkeys: Vec<Pubkey>,
+    pub sink: Arc<dyn AccountWriteSink>,
+    pub timeout_interval: Duration,
+}
+
+const ROUTE_CHANNEL_CAPACITY: usize = 10_000;
+
+// Each route gets its own task and channel so a slow or erroring sink never
+// stalls the gRPC broadcast loop in `send_loop`. Once a route's channel is
+// full, further writes for that route are dropped, not queued.
+fn spawn_route(route: SinkRoute) -> mpsc::Sender<(Pubkey, MessageAccountInfo)> {
+    let (tx, mut rx) = mpsc::channel(ROUTE_CHANNEL_CAPACITY);
+    let sink = route.sink;
+    let timeout_interval = route.timeout_interval;
+
+    tokio::spawn(async move {
+        let mut last_fired: HashMap<Pubkey, Instant> = HashMap::new();
+
+        while let Some((pubkey, account)) = rx.recv().await {
+            let now = Instant::now();
+            if let Some(last) = last_fired.get(&pubkey) {
+                if now.duration_since(*last) < timeout_interval {
+                    continue;
+                }
+            }
+            last_fired.insert(pubkey, now);
+
+            if let Err(error) = sink.process(&pubkey, &account).await {
+                error!("sink route failed to process {pubkey}: {error}");
+            }
+        }
+    });
+
+    tx
+}
+
+// Routes account writes to every sink whose `matched_pubkeys` includes the
+// account, debouncing per-route so a fast-updating pubkey fires at most once
+// per `timeout_interval`.
+pub struct SinkRouter {
+    routes: Vec<(HashSet<Pubkey>, mpsc::Sender<(Pubkey, MessageAccountInfo)>)>,
+}
+
+impl SinkRouter {
+    pub fn new(routes: Vec<SinkRoute>) -> Self {
+        let routes = routes
+            .into_iter()
+            .map(|route| {
+                let matched = route.matched_pubkeys.iter().copied().collect();
+                (matched, spawn_route(route))
+            })
+            .collect();
+        Self { routes }
+    }
+
+    pub fn route(&self, pubkey: &Pubkey, account: &MessageAccountInfo) {
+        for (matched, tx) in &self.routes {
+            if matched.contains(pubkey) {
+                let _ = tx.try_send((*pubkey, account.clone()));
+            }
+        }
+    }
+}